@@ -0,0 +1,349 @@
+//! Library API for loading a BERT-family embedding model from the Hugging Face Hub and
+//! running it. `main.rs` is a thin CLI wrapper over [`Embedder`] so the same loading and
+//! batching logic can be embedded directly in a search service.
+
+use crate::models::{roberta_position_offset, BertModel, Config, LoraConfig};
+use anyhow::{Error as E, Result};
+use backend_core::{ModelType, Pool};
+use candle_core::{DType, Device, IndexOp, Tensor};
+use candle_nn::VarBuilder;
+use hf_hub::{api::sync::Api, Repo, RepoType};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokenizers::Tokenizer;
+
+/// Which weight file to fetch from the model repo and which `VarBuilder` constructor to
+/// load it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightSource {
+    Safetensors,
+    Pytorch,
+}
+
+/// Configuration for [`Embedder::new`]. Mirrors the CLI flags in `main.rs`, minus the
+/// ones (`prompt`, `n`, `tracing`) that only make sense for the one-shot binary.
+pub struct EmbedderOptions {
+    pub model_id: String,
+    pub revision: String,
+    pub weight_source: WeightSource,
+    pub normalize_embeddings: bool,
+    /// Run on CPU rather than trying CUDA/Metal first.
+    pub cpu: bool,
+    /// Defaults to f16 on CUDA devices and f32 on Metal and CPU when unset.
+    pub dtype: Option<DType>,
+    pub query_prefix: Option<String>,
+    pub passage_prefix: Option<String>,
+    /// Hub repo holding a LoRA adapter (an `adapter_model.safetensors` with `lora_A`/
+    /// `lora_B` tensors) to apply on top of the base checkpoint. No adapter is loaded
+    /// when unset.
+    pub lora_repo: Option<String>,
+    pub lora_rank: usize,
+    pub lora_alpha: f64,
+}
+
+impl Default for EmbedderOptions {
+    fn default() -> Self {
+        Self {
+            model_id: "intfloat/multilingual-e5-base".to_string(),
+            revision: "main".to_string(),
+            weight_source: WeightSource::Safetensors,
+            normalize_embeddings: true,
+            cpu: false,
+            dtype: None,
+            query_prefix: None,
+            passage_prefix: None,
+            lora_repo: None,
+            lora_rank: 8,
+            lora_alpha: 16.0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelConfig {
+    architectures: Vec<String>,
+    model_type: String,
+    pad_token_id: usize,
+}
+
+/// A single entry of a sentence-transformers `modules.json`, e.g.
+/// `{"idx": 1, "name": "1", "path": "1_Pooling", "type": "sentence_transformers.models.Pooling"}`.
+#[derive(Debug, Deserialize)]
+struct ModuleEntry {
+    path: String,
+    #[serde(rename = "type")]
+    module_type: String,
+}
+
+/// Finds the `path` of the `Pooling` module declared in a sentence-transformers
+/// `modules.json`. Repos with extra preprocessing modules or custom numbering don't
+/// necessarily keep pooling at the conventional `1_Pooling`, so this is the source of
+/// truth `1_Pooling/config.json` is just a fallback guess for.
+fn pooling_module_path(modules_json: &str) -> Option<String> {
+    let modules: Vec<ModuleEntry> = serde_json::from_str(modules_json).ok()?;
+    modules
+        .into_iter()
+        .find(|module| module.module_type.contains("Pooling"))
+        .map(|module| module.path)
+}
+
+/// Subset of a sentence-transformers `1_Pooling/config.json` needed to pick a `Pool`
+/// variant. Sentence-transformers can set more than one `pooling_mode_*` flag to `true`
+/// depending on how the model card was exported; the precedence below (CLS, then mean,
+/// then max) matches sentence-transformers' own fallback order.
+#[derive(Debug, Default, Deserialize)]
+struct PoolingConfig {
+    #[serde(default)]
+    pooling_mode_cls_token: bool,
+    #[serde(default)]
+    pooling_mode_mean_tokens: bool,
+    #[serde(default)]
+    pooling_mode_max_tokens: bool,
+}
+
+impl PoolingConfig {
+    fn pool(&self) -> Option<Pool> {
+        if self.pooling_mode_cls_token {
+            Some(Pool::Cls)
+        } else if self.pooling_mode_mean_tokens {
+            Some(Pool::Mean)
+        } else if self.pooling_mode_max_tokens {
+            // `backend_core::Pool` has no max-pooling variant yet; fall back to mean
+            // rather than silently picking an unsupported one.
+            println!("warning: model declares max pooling, which isn't supported yet; falling back to mean pooling");
+            Some(Pool::Mean)
+        } else {
+            None
+        }
+    }
+}
+
+/// Subset of `config_sentence_transformers.json` needed for query/passage prefixes,
+/// e.g. intfloat/e5 models ship `{"prompts": {"query": "query: ", "passage": "passage: "}}`.
+#[derive(Debug, Default, Deserialize)]
+struct SentenceTransformersConfig {
+    #[serde(default)]
+    prompts: HashMap<String, String>,
+}
+
+/// A loaded embedding model ready to embed text. Holds everything `main.rs` used to
+/// build and thread through by hand: the model, tokenizer, device, pooling strategy and
+/// normalization flag.
+pub struct Embedder {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+    pool: Pool,
+    normalize: bool,
+    query_prefix: Option<String>,
+    passage_prefix: Option<String>,
+    position_offset: u32,
+}
+
+impl Embedder {
+    pub fn new(options: EmbedderOptions) -> Result<Self> {
+        let device = crate::device(options.cpu)?;
+        let dtype = crate::dtype(&device, options.dtype);
+
+        let repo = Repo::with_revision(
+            options.model_id.clone(),
+            RepoType::Model,
+            options.revision.clone(),
+        );
+        let (config_filename, tokenizer_filename, weights_filename, pooling_config, st_config) = {
+            let api = Api::new()?;
+            let api = api.repo(repo);
+            let config = api.get("config.json")?;
+            let tokenizer = api.get("tokenizer.json")?;
+            let weights = match options.weight_source {
+                WeightSource::Pytorch => api.get("pytorch_model.bin")?,
+                WeightSource::Safetensors => api.get("model.safetensors")?,
+            };
+            // modules.json and the pooling module's config.json only exist for models
+            // published through sentence-transformers; fall back to the old hardcoded
+            // defaults when a repo doesn't have them.
+            let modules_json = api
+                .get("modules.json")
+                .ok()
+                .and_then(|path| std::fs::read_to_string(path).ok());
+            let is_sentence_transformers_repo = modules_json.is_some();
+            let pooling_module_path = modules_json
+                .as_deref()
+                .and_then(pooling_module_path)
+                .unwrap_or_else(|| "1_Pooling".to_string());
+            let pooling_config = api
+                .get(&format!("{pooling_module_path}/config.json"))
+                .ok()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str::<PoolingConfig>(&json).ok());
+            if is_sentence_transformers_repo && pooling_config.is_none() {
+                println!(
+                    "warning: couldn't read pooling config from {pooling_module_path}/config.json; falling back to mean pooling"
+                );
+            }
+            let st_config = api
+                .get("config_sentence_transformers.json")
+                .ok()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str::<SentenceTransformersConfig>(&json).ok());
+            (config, tokenizer, weights, pooling_config, st_config)
+        };
+
+        let config = std::fs::read_to_string(config_filename)?;
+        let model_config: ModelConfig = serde_json::from_str(&config)?;
+        let config: Config = serde_json::from_str(&config)?;
+
+        // SPLADE-style models expose a masked-language-modelling head (`BertForMaskedLM`)
+        // instead of a plain encoder, so route those through the sparse `Pool::Splade`
+        // path. Otherwise prefer whatever the pooling module's config.json declares, and only
+        // fall back to mean pooling (the historical default for
+        // intfloat/multilingual-e5-base) when the repo doesn't ship one.
+        let pool = if model_config
+            .architectures
+            .iter()
+            .any(|architecture| architecture.ends_with("ForMaskedLM"))
+        {
+            Pool::Splade
+        } else {
+            pooling_config
+                .as_ref()
+                .and_then(PoolingConfig::pool)
+                .unwrap_or(Pool::Mean)
+        };
+        let model_type = ModelType::Embedding(pool);
+        let position_offset = roberta_position_offset(&model_config.model_type, model_config.pad_token_id);
+
+        let tokenizer = Tokenizer::from_file(tokenizer_filename).map_err(E::msg)?;
+
+        let query_prefix = options.query_prefix.clone().or_else(|| {
+            st_config.as_ref().and_then(|c| c.prompts.get("query").cloned())
+        });
+        let passage_prefix = options.passage_prefix.clone().or_else(|| {
+            st_config.as_ref().and_then(|c| c.prompts.get("passage").cloned())
+        });
+
+        let vb = match options.weight_source {
+            WeightSource::Pytorch => VarBuilder::from_pth(&weights_filename, dtype, &device)?,
+            WeightSource::Safetensors => unsafe {
+                VarBuilder::from_mmaped_safetensors(&[weights_filename], dtype, &device)?
+            },
+        };
+        println!("Starting model on {device:?} ({dtype:?})");
+
+        let lora = match &options.lora_repo {
+            Some(lora_repo) => {
+                let repo = Repo::with_revision(lora_repo.clone(), RepoType::Model, "main".to_string());
+                let api = Api::new()?;
+                let adapter_weights = api.repo(repo).get("adapter_model.safetensors")?;
+                let lora_vb = unsafe { VarBuilder::from_mmaped_safetensors(&[adapter_weights], dtype, &device)? };
+                Some((
+                    lora_vb,
+                    LoraConfig {
+                        rank: options.lora_rank,
+                        alpha: options.lora_alpha,
+                    },
+                ))
+            }
+            None => None,
+        };
+        let model = BertModel::load_with_lora(vb, &config, model_type, lora, &model_config.architectures)?;
+
+        Ok(Self {
+            model,
+            tokenizer,
+            device,
+            pool,
+            normalize: options.normalize_embeddings,
+            query_prefix,
+            passage_prefix,
+            position_offset,
+        })
+    }
+
+    pub fn pool(&self) -> Pool {
+        self.pool
+    }
+
+    pub fn query_prefix(&self) -> Option<&str> {
+        self.query_prefix.as_deref()
+    }
+
+    pub fn passage_prefix(&self) -> Option<&str> {
+        self.passage_prefix.as_deref()
+    }
+
+    pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed_batch(&[text])?.remove(0))
+    }
+
+    pub fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut tokenizer = self.tokenizer.clone();
+        tokenizer
+            .with_padding(None)
+            .with_truncation(None)
+            .map_err(E::msg)?;
+        let encodings = tokenizer.encode_batch(texts.to_vec(), true).map_err(E::msg)?;
+        let batch = crate::encodings_to_batch(&encodings, self.position_offset);
+
+        let ys = self.model.forward(batch)?;
+        // Sparse SPLADE vectors are already comparable via dot product; only dense
+        // pooled embeddings need L2 normalization before cosine-similarity search.
+        let ys = if self.normalize && !matches!(self.pool, Pool::Splade) {
+            normalize_l2(&ys)?
+        } else {
+            ys
+        };
+
+        (0..ys.dim(0)?)
+            .map(|row| Ok(ys.i(row)?.to_dtype(DType::F32)?.to_vec1::<f32>()?))
+            .collect()
+    }
+
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+}
+
+fn normalize_l2(v: &Tensor) -> Result<Tensor> {
+    Ok(v.broadcast_div(&v.sqr()?.sum_keepdim(1)?.sqrt()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pooling_config_prefers_cls_over_mean_and_max() {
+        let config = PoolingConfig {
+            pooling_mode_cls_token: true,
+            pooling_mode_mean_tokens: true,
+            pooling_mode_max_tokens: true,
+        };
+        assert!(matches!(config.pool(), Some(Pool::Cls)));
+    }
+
+    #[test]
+    fn pooling_config_prefers_mean_over_max() {
+        let config = PoolingConfig {
+            pooling_mode_cls_token: false,
+            pooling_mode_mean_tokens: true,
+            pooling_mode_max_tokens: true,
+        };
+        assert!(matches!(config.pool(), Some(Pool::Mean)));
+    }
+
+    #[test]
+    fn pooling_config_falls_back_to_mean_for_unsupported_max_pooling() {
+        let config = PoolingConfig {
+            pooling_mode_cls_token: false,
+            pooling_mode_mean_tokens: false,
+            pooling_mode_max_tokens: true,
+        };
+        assert!(matches!(config.pool(), Some(Pool::Mean)));
+    }
+
+    #[test]
+    fn pooling_config_is_none_when_nothing_is_set() {
+        assert!(PoolingConfig::default().pool().is_none());
+    }
+}
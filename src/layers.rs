@@ -0,0 +1,203 @@
+use candle_core::{Result, Tensor, D};
+use candle_nn::{Module, VarBuilder};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HiddenAct {
+    Gelu,
+    GeluNew,
+    Relu,
+}
+
+impl HiddenAct {
+    pub fn forward(&self, xs: &Tensor) -> Result<Tensor> {
+        match self {
+            Self::Gelu => xs.gelu_erf(),
+            Self::GeluNew => xs.gelu(),
+            Self::Relu => xs.relu(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Linear {
+    weight: Tensor,
+    bias: Option<Tensor>,
+}
+
+impl Linear {
+    pub fn new(weight: Tensor, bias: Option<Tensor>) -> Self {
+        Self { weight, bias }
+    }
+
+    pub fn weight(&self) -> &Tensor {
+        &self.weight
+    }
+}
+
+/// Matmuls `xs [.., in]` by `weight [out, in]`, broadcasting the weight over a leading
+/// batch dimension when `xs` is 3-D. Shared by `Linear` and `LoraLinear`, whose low-rank
+/// factors need the exact same broadcasting.
+fn matmul_weight(xs: &Tensor, weight: &Tensor) -> Result<Tensor> {
+    let w = match xs.dims() {
+        &[bsize, _, _] => weight.broadcast_left(bsize)?.t()?,
+        _ => weight.t()?,
+    };
+    xs.matmul(&w)
+}
+
+impl Module for Linear {
+    fn forward(&self, xs: &Tensor) -> Result<Tensor> {
+        let xs = matmul_weight(xs, &self.weight)?;
+        match &self.bias {
+            Some(bias) => xs.broadcast_add(bias),
+            None => Ok(xs),
+        }
+    }
+}
+
+pub fn linear(in_dim: usize, out_dim: usize, vb: VarBuilder) -> Result<Linear> {
+    let weight = vb.get((out_dim, in_dim), "weight")?;
+    let bias = vb.get(out_dim, "bias")?;
+    Ok(Linear::new(weight, Some(bias)))
+}
+
+/// A `Linear` layer with an optional frozen-base LoRA adapter: `y = x·Wᵀ + (alpha/r)·(x·Aᵀ)·Bᵀ`.
+/// The base weights are never updated here; only `a`/`b` come from the adapter checkpoint.
+#[derive(Debug)]
+pub struct LoraLinear {
+    base: Linear,
+    adapter: Option<(Tensor, Tensor, f64)>,
+}
+
+impl LoraLinear {
+    pub fn new(base: Linear) -> Self {
+        Self { base, adapter: None }
+    }
+
+    /// Attaches a LoRA adapter with low-rank factors `a [r, in]`, `b [out, r]` and scaling
+    /// `alpha / r`.
+    pub fn with_adapter(base: Linear, a: Tensor, b: Tensor, rank: usize, alpha: f64) -> Self {
+        Self {
+            base,
+            adapter: Some((a, b, alpha / rank as f64)),
+        }
+    }
+}
+
+impl Module for LoraLinear {
+    fn forward(&self, xs: &Tensor) -> Result<Tensor> {
+        let base = self.base.forward(xs)?;
+        match &self.adapter {
+            Some((a, b, scale)) => {
+                let delta = matmul_weight(xs, a)?;
+                let delta = matmul_weight(&delta, b)?;
+                base + (delta * *scale)?
+            }
+            None => Ok(base),
+        }
+    }
+}
+
+/// Builds a `LoraLinear`, attaching an adapter from `lora` (a `(adapter VarBuilder, rank,
+/// alpha)` triple scoped to this layer's tensors) when one is supplied. The adapter
+/// safetensors file is expected to mirror the base checkpoint's module path with
+/// `lora_A.weight [r, in]` / `lora_B.weight [out, r]` tensors alongside it. This is this
+/// crate's own naming convention, not the PEFT/candle-lora layout (which nests under a
+/// `base_model.model.*` prefix with an adapter-name segment, e.g. `lora_A.default.weight`);
+/// adapters exported from those tools need their keys renamed before loading here.
+pub fn lora_linear(
+    in_dim: usize,
+    out_dim: usize,
+    vb: VarBuilder,
+    lora: Option<(VarBuilder, usize, f64)>,
+) -> Result<LoraLinear> {
+    let base = linear(in_dim, out_dim, vb)?;
+    match lora {
+        Some((lora_vb, rank, alpha)) => {
+            let a = lora_vb.get((rank, in_dim), "lora_A.weight")?;
+            let b = lora_vb.get((out_dim, rank), "lora_B.weight")?;
+            Ok(LoraLinear::with_adapter(base, a, b, rank, alpha))
+        }
+        None => Ok(LoraLinear::new(base)),
+    }
+}
+
+#[derive(Debug)]
+pub struct LayerNorm {
+    weight: Tensor,
+    bias: Tensor,
+    eps: f64,
+}
+
+impl LayerNorm {
+    pub fn new(weight: Tensor, bias: Tensor, eps: f64) -> Self {
+        Self { weight, bias, eps }
+    }
+}
+
+impl Module for LayerNorm {
+    fn forward(&self, xs: &Tensor) -> Result<Tensor> {
+        let x_dtype = xs.dtype();
+        let internal_dtype = match x_dtype {
+            candle_core::DType::F16 | candle_core::DType::BF16 => candle_core::DType::F32,
+            d => d,
+        };
+        let (_bsize, _seq_len, hidden_size) = xs.dims3()?;
+        let xs = xs.to_dtype(internal_dtype)?;
+        let mean_x = (xs.sum_keepdim(D::Minus1)? / hidden_size as f64)?;
+        let xs = xs.broadcast_sub(&mean_x)?;
+        let norm_x = (xs.sqr()?.sum_keepdim(D::Minus1)? / hidden_size as f64)?;
+        let xs_normed = xs.broadcast_div(&(norm_x + self.eps)?.sqrt()?)?;
+        let xs_normed = xs_normed.to_dtype(x_dtype)?;
+        xs_normed
+            .broadcast_mul(&self.weight)?
+            .broadcast_add(&self.bias)
+    }
+}
+
+pub fn layer_norm(size: usize, eps: f64, vb: VarBuilder) -> Result<LayerNorm> {
+    let weight = vb.get(size, "weight")?;
+    let bias = vb.get(size, "bias")?;
+    Ok(LayerNorm::new(weight, bias, eps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_core::Device;
+
+    #[test]
+    fn lora_linear_adds_scaled_low_rank_delta() -> Result<()> {
+        let device = Device::Cpu;
+        let weight = Tensor::from_vec(vec![1f32, 1f32], (1, 2), &device)?;
+        let base = Linear::new(weight, None);
+
+        // rank-1 adapter: a [1, 2], b [1, 1], alpha 4.0 -> scale = alpha / rank = 4.0
+        let a = Tensor::from_vec(vec![1f32, 0f32], (1, 2), &device)?;
+        let b = Tensor::from_vec(vec![2f32], (1, 1), &device)?;
+        let lora = LoraLinear::with_adapter(base, a, b, 1, 4.0);
+
+        let xs = Tensor::from_vec(vec![1f32, 1f32], (1, 2), &device)?;
+        let ys = lora.forward(&xs)?.flatten_all()?.to_vec1::<f32>()?;
+
+        // base: 1*1 + 1*1 = 2; delta: ((1*1 + 1*0) * 2) * 4.0 = 8; total = 10
+        assert_eq!(ys, vec![10f32]);
+        Ok(())
+    }
+
+    #[test]
+    fn lora_linear_without_adapter_matches_base() -> Result<()> {
+        let device = Device::Cpu;
+        let weight = Tensor::from_vec(vec![1f32, 1f32], (1, 2), &device)?;
+        let base = Linear::new(weight, None);
+        let lora = LoraLinear::new(base);
+
+        let xs = Tensor::from_vec(vec![1f32, 1f32], (1, 2), &device)?;
+        let ys = lora.forward(&xs)?.flatten_all()?.to_vec1::<f32>()?;
+
+        assert_eq!(ys, vec![2f32]);
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+pub mod embedder;
+pub mod layers;
+pub mod models;
+pub mod reranker;
+
+pub use embedder::{Embedder, EmbedderOptions, WeightSource};
+pub use reranker::{Reranker, RerankerOptions};
+
+use anyhow::Result;
+use backend_core::Batch;
+use candle_core::{DType, Device};
+use std::cmp::max;
+use tokenizers::Encoding;
+
+/// Picks a compute device: CUDA or Metal if available and not forced to CPU, else CPU.
+/// Shared by [`Embedder`] and [`Reranker`], which otherwise both load a model with the
+/// same device-selection logic.
+pub(crate) fn device(cpu: bool) -> Result<Device> {
+    if cpu {
+        return Ok(Device::Cpu);
+    }
+    if let Ok(device) = Device::new_cuda(0) {
+        return Ok(device);
+    }
+    if let Ok(device) = Device::new_metal(0) {
+        return Ok(device);
+    }
+    println!("Running on CPU, no CUDA or Metal device found");
+    Ok(Device::Cpu)
+}
+
+/// Defaults to f16 on CUDA devices and f32 on Metal and CPU when `dtype` is unset.
+pub(crate) fn dtype(device: &Device, dtype: Option<DType>) -> DType {
+    match dtype {
+        Some(dtype) => dtype,
+        None if device.is_cuda() => DType::F16,
+        None => DType::F32,
+    }
+}
+
+/// Flattens tokenizer `Encoding`s into the `Batch` representation `BertModel`/
+/// `BertClassifier` expect, assigning each sequence `position_offset..position_offset +
+/// seq_len` position ids. Shared by [`Embedder`] and [`Reranker`], which otherwise both
+/// rebuild the same `cumulative_seq_lengths`/`max_length` bookkeeping.
+pub(crate) fn encodings_to_batch(encodings: &[Encoding], position_offset: u32) -> Batch {
+    let mut input_ids = Vec::new();
+    let mut token_type_ids = Vec::new();
+    let mut position_ids = Vec::new();
+    let mut cumulative_seq_lengths = Vec::with_capacity(encodings.len() + 1);
+    cumulative_seq_lengths.push(0);
+    let mut current_tokens = 0;
+    let mut max_length = 0;
+
+    for encoding in encodings {
+        let seq_len = encoding.len();
+        input_ids.extend(encoding.get_ids().to_vec());
+        token_type_ids.extend(encoding.get_type_ids().to_vec());
+        position_ids.extend(position_offset..position_offset + seq_len as u32);
+
+        current_tokens += seq_len;
+        max_length = max(max_length, seq_len as u32);
+        cumulative_seq_lengths.push(current_tokens as u32);
+    }
+
+    Batch {
+        input_ids,
+        token_type_ids,
+        position_ids,
+        cumulative_seq_lengths,
+        max_length,
+    }
+}
@@ -1,18 +1,8 @@
-mod layers;
-mod models;
-
-use models::{BertModel, Config};
-use candle_transformers::models::bert::DTYPE;
-use anyhow::{Error as E, Result};
-use candle_core::{Tensor, Device};
-use candle_nn::VarBuilder;
+use anyhow::Result;
+use candle_core::DType;
 use clap::Parser;
-use hf_hub::{api::sync::Api, Repo, RepoType};
-use tokenizers::tokenizer::Tokenizer;
-use std::collections::HashMap;
-use serde::Deserialize;
-use std::cmp::max;
-use backend_core::{Batch, ModelType, Pool};
+use rusty_embeddings::{Embedder, EmbedderOptions, Reranker, RerankerOptions, WeightSource};
+use backend_core::Pool;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -47,71 +37,146 @@ struct Args {
     /// L2 normalization for embeddings.
     #[arg(long, default_value = "true")]
     normalize_embeddings: bool,
+
+    /// Floating point type to run the model in. Defaults to f16 on CUDA devices (CUDA
+    /// supports half-precision BERT kernels in candle) and f32 on Metal and CPU.
+    #[arg(long, value_enum)]
+    dtype: Option<DTypeArg>,
+
+    /// Prefix prepended to query inputs before tokenization, e.g. "query: " for e5
+    /// models. Overrides whatever `config_sentence_transformers.json` declares.
+    #[arg(long)]
+    query_prefix: Option<String>,
+
+    /// Prefix prepended to passage inputs before tokenization, e.g. "passage: " for e5
+    /// models. Overrides whatever `config_sentence_transformers.json` declares.
+    #[arg(long)]
+    passage_prefix: Option<String>,
+
+    /// Hub repo holding a LoRA adapter (`adapter_model.safetensors`) to apply on top of
+    /// the base checkpoint's attention query/value and FFN intermediate/output layers.
+    #[arg(long)]
+    lora_repo: Option<String>,
+
+    /// Rank `r` of the LoRA adapter's low-rank factors.
+    #[arg(long, default_value = "8")]
+    lora_rank: usize,
+
+    /// LoRA scaling numerator; the applied scale is `lora_alpha / lora_rank`.
+    #[arg(long, default_value = "16")]
+    lora_alpha: f64,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Cross-encoder rerank: score each passage against the query with the model's
+    /// sequence-classification head and print them sorted by relevance.
+    Rerank {
+        #[arg(long)]
+        query: String,
+
+        #[arg(long, value_delimiter = ',')]
+        passages: Vec<String>,
+    },
+}
 
-fn device(cpu: bool) -> Result<Device> {
-    Ok(Device::Cpu)
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum DTypeArg {
+    F32,
+    F16,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ModelConfig {
-    pub architectures: Vec<String>,
-    pub model_type: String,
-    #[serde(alias = "n_positions")]
-    pub max_position_embeddings: usize,
-    pub pad_token_id: usize,
-    pub id2label: Option<HashMap<String, String>>,
-    pub label2id: Option<HashMap<String, usize>>,
+impl From<DTypeArg> for DType {
+    fn from(dtype: DTypeArg) -> Self {
+        match dtype {
+            DTypeArg::F32 => DType::F32,
+            DTypeArg::F16 => DType::F16,
+        }
+    }
 }
 
 impl Args {
-    fn build_model_and_tokenizer(&self) -> Result<(BertModel, Tokenizer)> {
-        // let device = candle_examples::device(self.cpu)?;
-        let device = device(self.cpu)?;
-        let default_model = "intfloat/multilingual-e5-base".to_string();
-        // let default_model = "sentence-transformers/all-MiniLM-L6-v2".to_string();
-        let default_revision = "main".to_string();
-        // let default_revision = "refs/pr/21".to_string();
-        let (model_id, revision) = match (self.model_id.to_owned(), self.revision.to_owned()) {
-            (Some(model_id), Some(revision)) => (model_id, revision),
-            (Some(model_id), None) => (model_id, "main".to_string()),
-            (None, Some(revision)) => (default_model, revision),
-            (None, None) => (default_model, default_revision),
-        };
-
-        let repo = Repo::with_revision(model_id, RepoType::Model, revision);
-        let (config_filename, tokenizer_filename, weights_filename) = {
-            let api = Api::new()?;
-            let api = api.repo(repo);
-            let config = api.get("config.json")?;
-            let tokenizer = api.get("tokenizer.json")?;
-            let weights = if self.use_pth {
-                api.get("pytorch_model.bin")?
+    fn embedder_options(&self) -> EmbedderOptions {
+        let defaults = EmbedderOptions::default();
+        EmbedderOptions {
+            model_id: self.model_id.clone().unwrap_or(defaults.model_id),
+            revision: self.revision.clone().unwrap_or(defaults.revision),
+            weight_source: if self.use_pth {
+                WeightSource::Pytorch
             } else {
-                api.get("model.safetensors")?
-            };
-            (config, tokenizer, weights)
-        };
-        let config = std::fs::read_to_string(config_filename)?;
-        println!("config from JSON {}", &config);
-        let config: Config = serde_json::from_str(&config)?;
-        // Set pooling config
-        let pool = Pool::Mean; // for intfloat/multilingual-e5-base
-        let model_type = ModelType::Embedding(pool);
-        let tokenizer = Tokenizer::from_file(tokenizer_filename).map_err(E::msg)?;
-
-        let vb = if self.use_pth {
-            VarBuilder::from_pth(&weights_filename, DTYPE, &device)?
-        } else {
-            unsafe { VarBuilder::from_mmaped_safetensors(&[weights_filename], DTYPE, &device)? }
-        };
-        println!("Starting model on CPU");
-        let model = BertModel::load(vb, &config, model_type)?;
-        Ok((model, tokenizer))
+                WeightSource::Safetensors
+            },
+            normalize_embeddings: self.normalize_embeddings,
+            cpu: self.cpu,
+            dtype: self.dtype.map(DType::from),
+            query_prefix: self.query_prefix.clone(),
+            passage_prefix: self.passage_prefix.clone(),
+            lora_repo: self.lora_repo.clone(),
+            lora_rank: self.lora_rank,
+            lora_alpha: self.lora_alpha,
+        }
+    }
+
+    fn reranker_options(&self) -> RerankerOptions {
+        let defaults = RerankerOptions::default();
+        RerankerOptions {
+            model_id: self.model_id.clone().unwrap_or(defaults.model_id),
+            revision: self.revision.clone().unwrap_or(defaults.revision),
+            cpu: self.cpu,
+            dtype: self.dtype.map(DType::from),
+        }
     }
 }
 
+fn run_rerank(args: &Args, query: &str, passages: &[String]) -> Result<()> {
+    let reranker = Reranker::new(args.reranker_options())?;
+    let passages: Vec<&str> = passages.iter().map(String::as_str).collect();
+    let ranked = reranker.rerank(query, &passages)?;
+
+    for (rank, (index, score)) in ranked.iter().enumerate() {
+        println!("{}. ({score:.4}) {}", rank + 1, passages[*index]);
+    }
+    Ok(())
+}
+
+fn run_embed(args: &Args) -> Result<()> {
+    let embedder = Embedder::new(args.embedder_options())?;
+    let is_sparse = matches!(embedder.pool(), Pool::Splade);
+
+    let base = args
+        .prompt
+        .clone()
+        .unwrap_or_else(|| "This framework generates embeddings for each input sentence".to_string());
+    // Demo a batch mixing both prefixes: the same text once as a query and once as a
+    // passage, since that's the asymmetric case `--query-prefix`/`--passage-prefix` and
+    // the sentence-transformers auto-detection exist for (e.g. intfloat/e5 models).
+    let query_sentence = match embedder.query_prefix() {
+        Some(prefix) => format!("{prefix}{base}"),
+        None => base.clone(),
+    };
+    let passage_sentence = match embedder.passage_prefix() {
+        Some(prefix) => format!("{prefix}{base}"),
+        None => base,
+    };
+    let sentences = vec![query_sentence.as_str(), passage_sentence.as_str()];
+
+    println!("constructed batch from input");
+    let ys = embedder.embed_batch(&sentences)?;
+
+    if is_sparse {
+        for (row, sparse) in ys.iter().enumerate() {
+            let pairs = rusty_embeddings::models::sparse_vector_to_pairs(sparse);
+            println!("sentence {row}: {} nonzero dimensions", pairs.len());
+        }
+    } else {
+        println!("pooled embeddings {ys:?}");
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     use tracing_chrome::ChromeLayerBuilder;
     use tracing_subscriber::prelude::*;
@@ -127,78 +192,12 @@ fn main() -> Result<()> {
     };
     let start = std::time::Instant::now();
 
-    let (model, mut tokenizer) = args.build_model_and_tokenizer()?;
-
-    
-    let sentences = [
-        "This framework generates embeddings for each input sentence",
-        "This framework generates embeddings for each input sentence",
-    ];
-
-    // let sentences = [
-    //     // "The cat sits outside",
-    //     // "A man is playing guitar",
-    //     // "I love pasta",
-    //     // "The new movie is awesome",
-    //     // "The cat plays in the garden",
-    //     // "A woman watches TV",
-    //     // "The new movie is so great",
-    //     // "Do you like pizza?",
-    // ];
-    let tokenizer = tokenizer
-        .with_padding(None)
-        .with_truncation(None)
-        .map_err(E::msg)?;
-    
-    let encodings = tokenizer
-        .encode_batch(sentences.to_vec(), true)
-        .map_err(E::msg)?;
-
-    let capacity = 100;
-    let max_batch_tokens = 1000;
-    let mut input_ids = Vec::with_capacity(max_batch_tokens);
-    let mut token_type_ids = Vec::with_capacity(max_batch_tokens);
-    let mut position_ids = Vec::with_capacity(max_batch_tokens);
-    let mut cu_seq_lengths = Vec::with_capacity(capacity);
-    cu_seq_lengths.push(0);
-    let mut current_tokens = 0;
-    let mut max_length = 0;
-
-    let position_offset = 2; // for roberta
-    for encoding in encodings {
-        let seq_len = encoding.len();
-        input_ids.extend(encoding.get_ids().to_vec());
-        token_type_ids.extend(encoding.get_type_ids().to_vec());
-        position_ids.extend((position_offset as u32..(seq_len + position_offset) as u32)
-        .collect::<Vec<_>>(),);
-    
-        let entry_tokens = encoding.get_ids().to_vec().len();
-        current_tokens += entry_tokens;
-        max_length = max(max_length, entry_tokens as u32);
-        cu_seq_lengths.push(current_tokens as u32);
+    match &args.command {
+        Some(Command::Rerank { query, passages }) => run_rerank(&args, query, passages)?,
+        None => run_embed(&args)?,
     }
 
-    let batch = Batch {
-        input_ids,
-        token_type_ids,
-        position_ids,
-        cumulative_seq_lengths: cu_seq_lengths,
-        max_length,
-    };
-
-    println!("constructed batch from input");
-    let ys = model.forward(batch)?;
-
-    let embeddings =  normalize_l2(&ys)?;
-    println!("pooled embeddings {embeddings}");
-    // dbg!(embeddings);
+    println!("Took {:?}", start.elapsed());
 
-    println!("Took {:?}", start.elapsed()); //to_vecX()
-
-   
     Ok(())
 }
-
-pub fn normalize_l2(v: &Tensor) -> Result<Tensor> {
-    Ok(v.broadcast_div(&v.sqr()?.sum_keepdim(1)?.sqrt()?)?)
-}
\ No newline at end of file
@@ -0,0 +1,571 @@
+use crate::layers::{layer_norm, linear, lora_linear, HiddenAct, LayerNorm, Linear, LoraLinear};
+use anyhow::{bail, Result};
+use backend_core::{Batch, ModelType, Pool};
+use candle_core::{DType, Device, IndexOp, Tensor, D};
+use candle_nn::{Embedding, Module, VarBuilder};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub vocab_size: usize,
+    pub hidden_size: usize,
+    pub num_hidden_layers: usize,
+    pub num_attention_heads: usize,
+    pub intermediate_size: usize,
+    pub hidden_act: HiddenAct,
+    pub max_position_embeddings: usize,
+    pub type_vocab_size: usize,
+    pub layer_norm_eps: f64,
+    pub pad_token_id: usize,
+}
+
+/// LoRA hyperparameters for an adapter checkpoint loaded alongside the base model.
+#[derive(Debug, Clone, Copy)]
+pub struct LoraConfig {
+    pub rank: usize,
+    pub alpha: f64,
+}
+
+/// Whether `architectures` names a checkpoint that wraps the base BERT encoder in a task
+/// head (`BertForMaskedLM`, `BertForSequenceClassification`), which safetensors nest the
+/// encoder's tensors under a `bert.*` prefix, sibling to the head's own tensors
+/// (`cls.predictions.*`, `classifier.*`) at the checkpoint root. Plain `BertModel`/
+/// `AutoModel` checkpoints (e.g. intfloat/multilingual-e5-base) have no such wrapper and
+/// keep the encoder at the root.
+fn has_wrapped_encoder(architectures: &[String]) -> bool {
+    architectures
+        .iter()
+        .any(|architecture| architecture.ends_with("ForMaskedLM") || architecture.ends_with("ForSequenceClassification"))
+}
+
+/// RoBERTa-family models (`model_type` containing `"roberta"`) start position ids at
+/// `padding_idx + 1` rather than `0`; every other architecture, including plain BERT and
+/// the `ForMaskedLM` SPLADE checkpoints, uses the ordinary `0`-based position ids. Shared
+/// by [`crate::Embedder`] and [`crate::Reranker`], which both tokenize batches outside
+/// this module and need the same offset.
+pub fn roberta_position_offset(model_type: &str, pad_token_id: usize) -> u32 {
+    if model_type.contains("roberta") {
+        pad_token_id as u32 + 1
+    } else {
+        0
+    }
+}
+
+fn embedding(vocab_size: usize, hidden_size: usize, vb: VarBuilder) -> Result<Embedding> {
+    let weight = vb.get((vocab_size, hidden_size), "weight")?;
+    Ok(Embedding::new(weight, hidden_size))
+}
+
+struct BertEmbeddings {
+    word_embeddings: Embedding,
+    position_embeddings: Embedding,
+    token_type_embeddings: Embedding,
+    layer_norm: LayerNorm,
+}
+
+impl BertEmbeddings {
+    fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        Ok(Self {
+            word_embeddings: embedding(config.vocab_size, config.hidden_size, vb.pp("word_embeddings"))?,
+            position_embeddings: embedding(
+                config.max_position_embeddings,
+                config.hidden_size,
+                vb.pp("position_embeddings"),
+            )?,
+            token_type_embeddings: embedding(
+                config.type_vocab_size,
+                config.hidden_size,
+                vb.pp("token_type_embeddings"),
+            )?,
+            layer_norm: layer_norm(config.hidden_size, config.layer_norm_eps, vb.pp("LayerNorm"))?,
+        })
+    }
+
+    fn forward(&self, input_ids: &Tensor, token_type_ids: &Tensor, position_ids: &Tensor) -> Result<Tensor> {
+        let input_embeddings = self.word_embeddings.forward(input_ids)?;
+        let token_type_embeddings = self.token_type_embeddings.forward(token_type_ids)?;
+        let position_embeddings = self.position_embeddings.forward(position_ids)?;
+        let embeddings = (input_embeddings + token_type_embeddings)?;
+        let embeddings = embeddings.broadcast_add(&position_embeddings)?;
+        Ok(self.layer_norm.forward(&embeddings)?)
+    }
+}
+
+struct BertSelfAttention {
+    query: LoraLinear,
+    key: Linear,
+    value: LoraLinear,
+    num_attention_heads: usize,
+    attention_head_size: usize,
+}
+
+impl BertSelfAttention {
+    fn load(vb: VarBuilder, config: &Config, lora: Option<&(VarBuilder, LoraConfig)>) -> Result<Self> {
+        let attention_head_size = config.hidden_size / config.num_attention_heads;
+        let all_head_size = config.num_attention_heads * attention_head_size;
+        Ok(Self {
+            query: lora_linear(
+                config.hidden_size,
+                all_head_size,
+                vb.pp("query"),
+                lora.map(|(vb, cfg)| (vb.pp("query"), cfg.rank, cfg.alpha)),
+            )?,
+            key: linear(config.hidden_size, all_head_size, vb.pp("key"))?,
+            value: lora_linear(
+                config.hidden_size,
+                all_head_size,
+                vb.pp("value"),
+                lora.map(|(vb, cfg)| (vb.pp("value"), cfg.rank, cfg.alpha)),
+            )?,
+            num_attention_heads: config.num_attention_heads,
+            attention_head_size,
+        })
+    }
+
+    fn transpose_for_scores(&self, xs: &Tensor) -> Result<Tensor> {
+        let (bsize, seq_len, _) = xs.dims3()?;
+        let xs = xs.reshape((bsize, seq_len, self.num_attention_heads, self.attention_head_size))?;
+        Ok(xs.transpose(1, 2)?.contiguous()?)
+    }
+
+    fn forward(&self, hidden_states: &Tensor, attention_mask: &Tensor) -> Result<Tensor> {
+        let query = self.transpose_for_scores(&self.query.forward(hidden_states)?)?;
+        let key = self.transpose_for_scores(&self.key.forward(hidden_states)?)?;
+        let value = self.transpose_for_scores(&self.value.forward(hidden_states)?)?;
+
+        let scale = 1f64 / (self.attention_head_size as f64).sqrt();
+        let scores = (query.matmul(&key.transpose(D::Minus1, D::Minus2)?)? * scale)?;
+        let scores = scores.broadcast_add(attention_mask)?;
+        let probs = candle_nn::ops::softmax_last_dim(&scores)?;
+        let context = probs.matmul(&value)?;
+        let context = context.transpose(1, 2)?.contiguous()?;
+        let (bsize, seq_len, _, _) = context.dims4()?;
+        Ok(context.reshape((bsize, seq_len, self.num_attention_heads * self.attention_head_size))?)
+    }
+}
+
+struct BertSelfOutput {
+    dense: Linear,
+    layer_norm: LayerNorm,
+}
+
+impl BertSelfOutput {
+    fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        Ok(Self {
+            dense: linear(config.hidden_size, config.hidden_size, vb.pp("dense"))?,
+            layer_norm: layer_norm(config.hidden_size, config.layer_norm_eps, vb.pp("LayerNorm"))?,
+        })
+    }
+
+    fn forward(&self, hidden_states: &Tensor, input_tensor: &Tensor) -> Result<Tensor> {
+        let hidden_states = self.dense.forward(hidden_states)?;
+        Ok(self.layer_norm.forward(&(hidden_states + input_tensor)?)?)
+    }
+}
+
+struct BertAttention {
+    self_attention: BertSelfAttention,
+    output: BertSelfOutput,
+}
+
+impl BertAttention {
+    fn load(vb: VarBuilder, config: &Config, lora: Option<&(VarBuilder, LoraConfig)>) -> Result<Self> {
+        let self_lora = lora.map(|(vb, cfg)| (vb.pp("self"), *cfg));
+        Ok(Self {
+            self_attention: BertSelfAttention::load(vb.pp("self"), config, self_lora.as_ref())?,
+            output: BertSelfOutput::load(vb.pp("output"), config)?,
+        })
+    }
+
+    fn forward(&self, hidden_states: &Tensor, attention_mask: &Tensor) -> Result<Tensor> {
+        let self_outputs = self.self_attention.forward(hidden_states, attention_mask)?;
+        self.output.forward(&self_outputs, hidden_states)
+    }
+}
+
+struct BertIntermediate {
+    dense: LoraLinear,
+    intermediate_act: HiddenAct,
+}
+
+impl BertIntermediate {
+    fn load(vb: VarBuilder, config: &Config, lora: Option<&(VarBuilder, LoraConfig)>) -> Result<Self> {
+        Ok(Self {
+            dense: lora_linear(
+                config.hidden_size,
+                config.intermediate_size,
+                vb.pp("dense"),
+                lora.map(|(vb, cfg)| (vb.pp("dense"), cfg.rank, cfg.alpha)),
+            )?,
+            intermediate_act: config.hidden_act,
+        })
+    }
+
+    fn forward(&self, hidden_states: &Tensor) -> Result<Tensor> {
+        let hidden_states = self.dense.forward(hidden_states)?;
+        Ok(self.intermediate_act.forward(&hidden_states)?)
+    }
+}
+
+struct BertOutput {
+    dense: LoraLinear,
+    layer_norm: LayerNorm,
+}
+
+impl BertOutput {
+    fn load(vb: VarBuilder, config: &Config, lora: Option<&(VarBuilder, LoraConfig)>) -> Result<Self> {
+        Ok(Self {
+            dense: lora_linear(
+                config.intermediate_size,
+                config.hidden_size,
+                vb.pp("dense"),
+                lora.map(|(vb, cfg)| (vb.pp("dense"), cfg.rank, cfg.alpha)),
+            )?,
+            layer_norm: layer_norm(config.hidden_size, config.layer_norm_eps, vb.pp("LayerNorm"))?,
+        })
+    }
+
+    fn forward(&self, hidden_states: &Tensor, input_tensor: &Tensor) -> Result<Tensor> {
+        let hidden_states = self.dense.forward(hidden_states)?;
+        Ok(self.layer_norm.forward(&(hidden_states + input_tensor)?)?)
+    }
+}
+
+struct BertLayer {
+    attention: BertAttention,
+    intermediate: BertIntermediate,
+    output: BertOutput,
+}
+
+impl BertLayer {
+    fn load(vb: VarBuilder, config: &Config, lora: Option<&(VarBuilder, LoraConfig)>) -> Result<Self> {
+        let attention_lora = lora.map(|(vb, cfg)| (vb.pp("attention"), *cfg));
+        let intermediate_lora = lora.map(|(vb, cfg)| (vb.pp("intermediate"), *cfg));
+        let output_lora = lora.map(|(vb, cfg)| (vb.pp("output"), *cfg));
+        Ok(Self {
+            attention: BertAttention::load(vb.pp("attention"), config, attention_lora.as_ref())?,
+            intermediate: BertIntermediate::load(vb.pp("intermediate"), config, intermediate_lora.as_ref())?,
+            output: BertOutput::load(vb.pp("output"), config, output_lora.as_ref())?,
+        })
+    }
+
+    fn forward(&self, hidden_states: &Tensor, attention_mask: &Tensor) -> Result<Tensor> {
+        let attention_output = self.attention.forward(hidden_states, attention_mask)?;
+        let intermediate_output = self.intermediate.forward(&attention_output)?;
+        self.output.forward(&intermediate_output, &attention_output)
+    }
+}
+
+struct BertEncoder {
+    layers: Vec<BertLayer>,
+}
+
+impl BertEncoder {
+    fn load(vb: VarBuilder, config: &Config, lora: Option<&(VarBuilder, LoraConfig)>) -> Result<Self> {
+        let layers = (0..config.num_hidden_layers)
+            .map(|index| {
+                let layer_lora = lora.map(|(vb, cfg)| (vb.pp(format!("layer.{index}")), *cfg));
+                BertLayer::load(vb.pp(format!("layer.{index}")), config, layer_lora.as_ref())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { layers })
+    }
+
+    fn forward(&self, hidden_states: &Tensor, attention_mask: &Tensor) -> Result<Tensor> {
+        let mut hidden_states = hidden_states.clone();
+        for layer in self.layers.iter() {
+            hidden_states = layer.forward(&hidden_states, attention_mask)?;
+        }
+        Ok(hidden_states)
+    }
+}
+
+/// Masked-language-modelling head tied to the input word embeddings, used to produce
+/// per-token vocabulary logits for SPLADE-style sparse embeddings.
+struct BertMLMHead {
+    dense: Linear,
+    hidden_act: HiddenAct,
+    layer_norm: LayerNorm,
+    decoder_bias: Tensor,
+}
+
+impl BertMLMHead {
+    fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        let dense = linear(config.hidden_size, config.hidden_size, vb.pp("transform.dense"))?;
+        let layer_norm = layer_norm(
+            config.hidden_size,
+            config.layer_norm_eps,
+            vb.pp("transform.LayerNorm"),
+        )?;
+        let decoder_bias = vb.get(config.vocab_size, "bias")?;
+        Ok(Self {
+            dense,
+            hidden_act: config.hidden_act,
+            layer_norm,
+            decoder_bias,
+        })
+    }
+
+    /// Projects encoder hidden states `[bsize, seq_len, hidden]` onto the vocabulary,
+    /// reusing the input word-embedding matrix as the (tied) decoder weight.
+    fn forward(&self, hidden_states: &Tensor, word_embeddings: &Embedding) -> Result<Tensor> {
+        let hidden_states = self.dense.forward(hidden_states)?;
+        let hidden_states = self.hidden_act.forward(&hidden_states)?;
+        let hidden_states = self.layer_norm.forward(&hidden_states)?;
+        let (bsize, seq_len, hidden_size) = hidden_states.dims3()?;
+        let logits = hidden_states
+            .reshape((bsize * seq_len, hidden_size))?
+            .matmul(&word_embeddings.embeddings().t()?)?
+            .reshape((bsize, seq_len, ()))?;
+        Ok(logits.broadcast_add(&self.decoder_bias)?)
+    }
+}
+
+pub struct BertModel {
+    embeddings: BertEmbeddings,
+    encoder: BertEncoder,
+    mlm_head: Option<BertMLMHead>,
+    pool: Pool,
+    device: Device,
+    dtype: DType,
+}
+
+impl BertModel {
+    /// Loads a model with no LoRA adapter. See [`BertModel::load_with_lora`] to apply one.
+    pub fn load(vb: VarBuilder, config: &Config, model_type: ModelType, architectures: &[String]) -> Result<Self> {
+        Self::load_with_lora(vb, config, model_type, None, architectures)
+    }
+
+    /// Loads a model, optionally applying a LoRA adapter (from a separate adapter
+    /// safetensors checkpoint) on top of the attention query/value projections and the
+    /// FFN intermediate/output dense layers.
+    pub fn load_with_lora(
+        vb: VarBuilder,
+        config: &Config,
+        model_type: ModelType,
+        lora: Option<(VarBuilder, LoraConfig)>,
+        architectures: &[String],
+    ) -> Result<Self> {
+        let pool = match model_type {
+            ModelType::Embedding(pool) => pool,
+            ModelType::Classifier => bail!("`BertModel` only supports embedding model types, use a classification head for rerankers"),
+        };
+
+        let bert_vb = if has_wrapped_encoder(architectures) {
+            vb.pp("bert")
+        } else {
+            vb.clone()
+        };
+        let embeddings = BertEmbeddings::load(bert_vb.pp("embeddings"), config)?;
+        let encoder_lora = lora.as_ref().map(|(vb, cfg)| (vb.pp("encoder"), *cfg));
+        let encoder = BertEncoder::load(bert_vb.pp("encoder"), config, encoder_lora.as_ref())?;
+        let mlm_head = match pool {
+            Pool::Splade => Some(BertMLMHead::load(vb.pp("cls.predictions"), config)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            embeddings,
+            encoder,
+            mlm_head,
+            pool,
+            device: vb.device().clone(),
+            dtype: vb.dtype(),
+        })
+    }
+
+    pub fn forward(&self, batch: Batch) -> Result<Tensor> {
+        let (input_ids, token_type_ids, position_ids, attention_mask) =
+            to_padded_tensors(&batch, &self.device, self.dtype)?;
+
+        let embedding_output = self.embeddings.forward(&input_ids, &token_type_ids, &position_ids)?;
+        let hidden_states = self.encoder.forward(&embedding_output, &attention_mask)?;
+
+        match self.pool {
+            Pool::Cls => Ok(hidden_states.i((.., 0))?),
+            Pool::Mean => {
+                let mask = attention_mask
+                    .reshape((attention_mask.dim(0)?, attention_mask.dim(3)?, 1))?
+                    .eq(0f64)?
+                    .to_dtype(self.dtype)?;
+                let sum = hidden_states.broadcast_mul(&mask)?.sum(1)?;
+                let count = mask.sum(1)?;
+                Ok(sum.broadcast_div(&count)?)
+            }
+            Pool::Splade => {
+                let mlm_head = self
+                    .mlm_head
+                    .as_ref()
+                    .expect("Splade pooling requires the MLM head to be loaded");
+                let logits = mlm_head.forward(&hidden_states, &self.embeddings.word_embeddings)?;
+                let activated = (logits.relu()? + 1f64)?.log()?;
+                let mask = attention_mask
+                    .reshape((attention_mask.dim(0)?, attention_mask.dim(3)?, 1))?
+                    .eq(0f64)?
+                    .to_dtype(self.dtype)?;
+                let masked = activated.broadcast_mul(&mask)?;
+                Ok(masked.max(1)?)
+            }
+        }
+    }
+}
+
+/// Extracts the positive `(index, value)` pairs of a single SPLADE sparse embedding row,
+/// suitable for feeding an inverted index.
+pub fn sparse_vector_to_pairs(sparse: &[f32]) -> Vec<(usize, f32)> {
+    sparse
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| value > 0f32)
+        .map(|(index, &value)| (index, value))
+        .collect()
+}
+
+/// Rebuilds padded `[bsize, max_length]` tensors from the flattened `Batch` representation,
+/// returning `(input_ids, token_type_ids, position_ids, attention_mask)`. Shared by
+/// `BertModel` and `BertClassifier`, which only differ in what they do with the resulting
+/// encoder hidden states.
+fn to_padded_tensors(batch: &Batch, device: &Device, dtype: DType) -> Result<(Tensor, Tensor, Tensor, Tensor)> {
+    let batch_size = batch.cumulative_seq_lengths.len() - 1;
+    let max_length = batch.max_length as usize;
+
+    let mut input_ids = vec![0u32; batch_size * max_length];
+    let mut token_type_ids = vec![0u32; batch_size * max_length];
+    let mut position_ids = vec![0u32; batch_size * max_length];
+    let mut attention_mask = vec![f32::NEG_INFINITY; batch_size * max_length];
+
+    for i in 0..batch_size {
+        let start = batch.cumulative_seq_lengths[i] as usize;
+        let end = batch.cumulative_seq_lengths[i + 1] as usize;
+        let seq_len = end - start;
+        let row = i * max_length;
+        input_ids[row..row + seq_len].copy_from_slice(&batch.input_ids[start..end]);
+        token_type_ids[row..row + seq_len].copy_from_slice(&batch.token_type_ids[start..end]);
+        position_ids[row..row + seq_len].copy_from_slice(&batch.position_ids[start..end]);
+        attention_mask[row..row + seq_len].iter_mut().for_each(|v| *v = 0f32);
+    }
+
+    let input_ids = Tensor::from_vec(input_ids, (batch_size, max_length), device)?;
+    let token_type_ids = Tensor::from_vec(token_type_ids, (batch_size, max_length), device)?;
+    let position_ids = Tensor::from_vec(position_ids, (batch_size, max_length), device)?;
+    let attention_mask =
+        Tensor::from_vec(attention_mask, (batch_size, 1, 1, max_length), device)?.to_dtype(dtype)?;
+
+    Ok((input_ids, token_type_ids, position_ids, attention_mask))
+}
+
+/// Pools the `[CLS]` hidden state through a dense + tanh projection, matching
+/// `BertModel.pooler` in the reference HF implementation.
+struct BertPooler {
+    dense: Linear,
+}
+
+impl BertPooler {
+    fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        Ok(Self {
+            dense: linear(config.hidden_size, config.hidden_size, vb.pp("dense"))?,
+        })
+    }
+
+    fn forward(&self, hidden_states: &Tensor) -> Result<Tensor> {
+        let first_token = hidden_states.i((.., 0))?;
+        Ok(self.dense.forward(&first_token)?.tanh()?)
+    }
+}
+
+/// Sequence-classification head (BERT encoder + pooler + linear classifier) used to serve
+/// cross-encoder rerankers, driven by the `id2label`/`label2id` already parsed out of
+/// `config.json`.
+pub struct BertClassifier {
+    embeddings: BertEmbeddings,
+    encoder: BertEncoder,
+    pooler: BertPooler,
+    classifier: Linear,
+    num_labels: usize,
+    positive_label_index: usize,
+    device: Device,
+    dtype: DType,
+}
+
+impl BertClassifier {
+    /// `num_labels` comes from the size of `config.json`'s `id2label` map (1 for a plain
+    /// relevance-score reranker, more for e.g. NLI-style classifiers). `positive_label_index`
+    /// is the `id2label` index `relevance_scores` reports for multi-label models; callers
+    /// derive it from the label names (see `reranker::positive_label_index`) since this
+    /// type has no opinion on label naming conventions.
+    pub fn load(
+        vb: VarBuilder,
+        config: &Config,
+        num_labels: usize,
+        positive_label_index: usize,
+        architectures: &[String],
+    ) -> Result<Self> {
+        let bert_vb = if has_wrapped_encoder(architectures) {
+            vb.pp("bert")
+        } else {
+            vb.clone()
+        };
+        let embeddings = BertEmbeddings::load(bert_vb.pp("embeddings"), config)?;
+        let encoder = BertEncoder::load(bert_vb.pp("encoder"), config, None)?;
+        let pooler = BertPooler::load(bert_vb.pp("pooler"), config)?;
+        let classifier = linear(config.hidden_size, num_labels, vb.pp("classifier"))?;
+
+        Ok(Self {
+            embeddings,
+            encoder,
+            pooler,
+            classifier,
+            num_labels,
+            positive_label_index,
+            device: vb.device().clone(),
+            dtype: vb.dtype(),
+        })
+    }
+
+    /// Raw classification logits `[bsize, num_labels]` for a batch of already-tokenized
+    /// `(query, passage)` pairs.
+    pub fn forward(&self, batch: Batch) -> Result<Tensor> {
+        let (input_ids, token_type_ids, position_ids, attention_mask) =
+            to_padded_tensors(&batch, &self.device, self.dtype)?;
+        let embedding_output = self.embeddings.forward(&input_ids, &token_type_ids, &position_ids)?;
+        let hidden_states = self.encoder.forward(&embedding_output, &attention_mask)?;
+        let pooled = self.pooler.forward(&hidden_states)?;
+        Ok(self.classifier.forward(&pooled)?)
+    }
+
+    /// Relevance score per pair: `sigmoid(logit)` for single-label (binary) rerankers, or
+    /// the softmax probability of `positive_label_index` for multi-label ones.
+    pub fn relevance_scores(&self, batch: Batch) -> Result<Vec<f32>> {
+        let logits = self.forward(batch)?;
+        let scores = if self.num_labels == 1 {
+            candle_nn::ops::sigmoid(&logits)?.flatten_all()?
+        } else {
+            let probs = candle_nn::ops::softmax_last_dim(&logits)?;
+            probs.i((.., self.positive_label_index))?
+        };
+        Ok(scores.to_dtype(DType::F32)?.to_vec1::<f32>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roberta_position_offset_shifts_by_padding_idx_plus_one() {
+        assert_eq!(roberta_position_offset("roberta", 1), 2);
+        assert_eq!(roberta_position_offset("xlm-roberta", 1), 2);
+    }
+
+    #[test]
+    fn roberta_position_offset_is_zero_for_non_roberta_models() {
+        assert_eq!(roberta_position_offset("bert", 0), 0);
+        assert_eq!(roberta_position_offset("distilbert", 0), 0);
+    }
+
+    #[test]
+    fn sparse_vector_to_pairs_keeps_only_positive_entries() {
+        let sparse = vec![0.0, 0.5, 0.0, -1.0, 2.0];
+        assert_eq!(sparse_vector_to_pairs(&sparse), vec![(1, 0.5), (4, 2.0)]);
+    }
+}
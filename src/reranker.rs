@@ -0,0 +1,162 @@
+//! Cross-encoder reranking: scores `(query, passage)` pairs with the sequence-classification
+//! head implied by a model's `id2label`/`label2id` config, for the two-stage
+//! retrieve-then-rerank pattern.
+
+use crate::models::{roberta_position_offset, BertClassifier, Config};
+use anyhow::{Error as E, Result};
+use candle_core::DType;
+use candle_nn::VarBuilder;
+use hf_hub::{api::sync::Api, Repo, RepoType};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokenizers::Tokenizer;
+
+#[derive(Debug, Deserialize)]
+struct ModelConfig {
+    #[serde(default)]
+    architectures: Vec<String>,
+    #[serde(default)]
+    model_type: String,
+    #[serde(default)]
+    pad_token_id: usize,
+    #[serde(default)]
+    id2label: Option<HashMap<String, String>>,
+    #[serde(default)]
+    label2id: Option<HashMap<String, usize>>,
+}
+
+/// Picks the `id2label` index `relevance_scores` should report for a multi-label
+/// classifier, by matching `label2id` against the names cross-encoders conventionally
+/// give the positive/relevant class (`entailment`, `relevant`, `positive`, or the HF
+/// default `LABEL_1`). Falls back to the last label when none match, since label ordering
+/// is only a convention, not something `config.json` guarantees.
+fn positive_label_index(label2id: Option<&HashMap<String, usize>>, num_labels: usize) -> usize {
+    const POSITIVE_NAMES: [&str; 4] = ["entailment", "relevant", "positive", "label_1"];
+    label2id
+        .and_then(|label2id| {
+            POSITIVE_NAMES.iter().find_map(|name| {
+                label2id
+                    .iter()
+                    .find(|(label, _)| label.to_lowercase() == *name)
+                    .map(|(_, &index)| index)
+            })
+        })
+        .unwrap_or(num_labels - 1)
+}
+
+pub struct RerankerOptions {
+    pub model_id: String,
+    pub revision: String,
+    pub cpu: bool,
+    pub dtype: Option<DType>,
+}
+
+impl Default for RerankerOptions {
+    fn default() -> Self {
+        Self {
+            model_id: "cross-encoder/ms-marco-MiniLM-L-6-v2".to_string(),
+            revision: "main".to_string(),
+            cpu: false,
+            dtype: None,
+        }
+    }
+}
+
+pub struct Reranker {
+    model: BertClassifier,
+    tokenizer: Tokenizer,
+    position_offset: u32,
+}
+
+impl Reranker {
+    pub fn new(options: RerankerOptions) -> Result<Self> {
+        let device = crate::device(options.cpu)?;
+        let dtype = crate::dtype(&device, options.dtype);
+
+        let repo = Repo::with_revision(options.model_id, RepoType::Model, options.revision);
+        let (config_filename, tokenizer_filename, weights_filename) = {
+            let api = Api::new()?;
+            let api = api.repo(repo);
+            (
+                api.get("config.json")?,
+                api.get("tokenizer.json")?,
+                api.get("model.safetensors")?,
+            )
+        };
+
+        let config = std::fs::read_to_string(config_filename)?;
+        let model_config: ModelConfig = serde_json::from_str(&config)?;
+        let config: Config = serde_json::from_str(&config)?;
+        // Treat an explicit empty `id2label` map the same as an absent one rather than
+        // letting `num_labels` become 0, which would make `positive_label_index` underflow.
+        let num_labels = model_config
+            .id2label
+            .as_ref()
+            .map(|labels| labels.len())
+            .filter(|&len| len > 0)
+            .unwrap_or(1);
+        let positive_label_index = positive_label_index(model_config.label2id.as_ref(), num_labels);
+        let position_offset = roberta_position_offset(&model_config.model_type, model_config.pad_token_id);
+
+        let tokenizer = Tokenizer::from_file(tokenizer_filename).map_err(E::msg)?;
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[weights_filename], dtype, &device)? };
+        let model = BertClassifier::load(vb, &config, num_labels, positive_label_index, &model_config.architectures)?;
+
+        Ok(Self {
+            model,
+            tokenizer,
+            position_offset,
+        })
+    }
+
+    /// Scores `query` against every passage and returns `(passage_index, score)` sorted by
+    /// descending relevance.
+    pub fn rerank(&self, query: &str, passages: &[&str]) -> Result<Vec<(usize, f32)>> {
+        let mut tokenizer = self.tokenizer.clone();
+        tokenizer
+            .with_padding(None)
+            .with_truncation(None)
+            .map_err(E::msg)?;
+
+        let pairs: Vec<(&str, &str)> = passages.iter().map(|passage| (query, *passage)).collect();
+        let encodings = tokenizer.encode_batch(pairs, true).map_err(E::msg)?;
+        let batch = crate::encodings_to_batch(&encodings, self.position_offset);
+
+        let scores = self.model.relevance_scores(batch)?;
+        let mut ranked: Vec<(usize, f32)> = scores.into_iter().enumerate().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(ranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label2id(pairs: &[(&str, usize)]) -> HashMap<String, usize> {
+        pairs.iter().map(|&(label, index)| (label.to_string(), index)).collect()
+    }
+
+    #[test]
+    fn positive_label_index_matches_by_name() {
+        let labels = label2id(&[("contradiction", 0), ("entailment", 1), ("neutral", 2)]);
+        assert_eq!(positive_label_index(Some(&labels), 3), 1);
+    }
+
+    #[test]
+    fn positive_label_index_matches_case_insensitively() {
+        let labels = label2id(&[("LABEL_0", 0), ("LABEL_1", 1)]);
+        assert_eq!(positive_label_index(Some(&labels), 2), 1);
+    }
+
+    #[test]
+    fn positive_label_index_falls_back_to_last_label_without_a_name_match() {
+        let labels = label2id(&[("foo", 0), ("bar", 1)]);
+        assert_eq!(positive_label_index(Some(&labels), 2), 1);
+    }
+
+    #[test]
+    fn positive_label_index_falls_back_to_last_label_without_label2id() {
+        assert_eq!(positive_label_index(None, 3), 2);
+    }
+}